@@ -1,54 +1,243 @@
 // Importing necessary modules and types from standard libraries, tokio, and warp.
 use std::collections::HashMap;
+use std::convert::Infallible;
 use std::sync::Arc;
-use tokio::sync::{RwLock, oneshot};
+use std::time::{Duration, Instant};
+use serde::Serialize;
+use tokio::sync::{broadcast, RwLock};
+use warp::http::StatusCode;
 use warp::Filter;
 
-// Defining a type alias for the shared state. The state holds a HashMap mapping
-// unique identifiers (String) to oneshot senders that synchronize two parties.
-type SyncState = Arc<RwLock<HashMap<String, oneshot::Sender<()>>>>;
+// How long a barrier session is allowed to sit around waiting for stragglers before a
+// request on it times out.
+const WAIT_TIMEOUT: Duration = Duration::from_secs(10);
 
-// Asynchronous handler function that synchronizes two parties using a unique ID.
-async fn wait_for_second_party_handler(
-    id: String, // Unique identifier for the pair of parties.
+// How long an abandoned session (one that never reached its expected party count) is kept
+// around before the GC sweep evicts it, and how often that sweep runs.
+const SESSION_TTL: Duration = Duration::from_secs(60);
+const GC_INTERVAL: Duration = Duration::from_secs(30);
+
+// How long a *completed* session is kept around (rather than removed immediately) so a
+// straggler that arrives just after the barrier released can be told it missed the
+// session (`SessionFull`) instead of silently starting a brand-new one under the same id.
+// This is only evaluated by the `GC_INTERVAL` sweep, so the real worst-case retention is
+// `GC_INTERVAL + COMPLETED_SESSION_RETENTION`, not this value alone.
+const COMPLETED_SESSION_RETENTION: Duration = Duration::from_secs(5);
+
+// A single barrier session: how many parties are expected, how many have shown up so far,
+// the broadcast channel used to release every waiter at once, when the first party arrived
+// (used by the GC sweep to find stale never-completed sessions), and -- once the barrier
+// has released -- when that happened (used to evict it after `COMPLETED_SESSION_RETENTION`
+// while still rejecting latecomers in the meantime).
+struct BarrierSession {
+    expected: usize,
+    arrived: usize,
+    release: broadcast::Sender<()>,
+    created_at: Instant,
+    completed_at: Option<Instant>,
+}
+
+// Defining a type alias for the shared state. The state holds a HashMap mapping unique
+// identifiers (String) to the barrier session for that ID, whether still in progress or
+// recently completed and kept for the straggler grace period.
+type SyncState = Arc<RwLock<HashMap<String, BarrierSession>>>;
+
+// Asynchronous handler function that synchronizes `n` parties using a unique ID. The first
+// caller for a given `id` declares the expected party count; every later arrival is counted
+// until `n` parties are present, at which point all blocked requests are released together.
+async fn wait_for_barrier_handler(
+    id: String,      // Unique identifier for the barrier session.
+    n: usize,        // Expected number of parties for this session.
     state: SyncState, // Shared state that holds the synchronization data.
 ) -> Result<impl warp::Reply, warp::Rejection> {
-    // Create a one-shot channel for the synchronization process (this allows one party to signal the other).
-    let (tx, rx) = oneshot::channel();
+    if n == 0 {
+        return Err(warp::reject::custom(SyncError::InvalidPartyCount {
+            session_id: id,
+            detail: "party count must be at least 1".to_string(),
+        }));
+    }
 
-    {
-        // Lock the state for writing to modify the synchronization data.
+    // Lock the state for writing to modify the synchronization data. The block evaluates
+    // to the receiver this call needs to wait on; every other path (an error, or this being
+    // the party that completes the barrier) returns out of the function directly.
+    let mut rx = {
         let mut state_lock = state.write().await;
 
-        // Check if there is already a sender waiting for a second party.
-        if let Some(existing_tx) = state_lock.remove(&id) {
-            // If a second party exists, signal the first party that the second party has arrived.
-            let _ = existing_tx.send(()); 
-            return Ok(warp::reply::with_status("Both parties synced.", warp::http::StatusCode::OK));
+        match state_lock.get_mut(&id) {
+            Some(session) => {
+                // A second caller declaring a different party count for the same id is a
+                // caller error, not a race -- report it distinctly.
+                if session.expected != n {
+                    return Err(warp::reject::custom(SyncError::InvalidPartyCount {
+                        session_id: id,
+                        detail: format!(
+                            "session already expects {} parties, got {}",
+                            session.expected, n
+                        ),
+                    }));
+                }
+
+                // A session that already released is kept around for a short grace period
+                // (see `COMPLETED_SESSION_RETENTION`) specifically so a real latecomer --
+                // a third party on an id whose barrier already completed -- gets told it
+                // missed the session instead of silently opening a new one.
+                if session.completed_at.is_some() {
+                    return Err(warp::reject::custom(SyncError::SessionFull { session_id: id }));
+                }
+
+                session.arrived += 1;
+                if session.arrived >= session.expected {
+                    // Last party to arrive: release everyone blocked on the broadcast
+                    // channel. The session is kept (marked completed) rather than removed
+                    // immediately so the GC sweep can evict it after the grace period.
+                    let _ = session.release.send(());
+                    session.completed_at = Some(Instant::now());
+                    return Ok(barrier_synced_reply());
+                }
+
+                session.release.subscribe()
+            }
+            None => {
+                let (tx, waiter_rx) = broadcast::channel(1);
+
+                if n == 1 {
+                    // A barrier of one party is satisfied on arrival; no need to even
+                    // store a session for it.
+                    return Ok(barrier_synced_reply());
+                }
+
+                state_lock.insert(
+                    id.clone(),
+                    BarrierSession {
+                        expected: n,
+                        arrived: 1,
+                        release: tx,
+                        created_at: Instant::now(),
+                        completed_at: None,
+                    },
+                );
+                waiter_rx
+            }
         }
+    };
 
-        // If no second party exists, store the current party's sender in the state.
-        state_lock.insert(id.clone(), tx);
+    // Wait for the barrier to complete or time out, whichever happens first.
+    tokio::select! {
+        result = rx.recv() => match result {
+            Ok(_) => Ok(barrier_synced_reply()),
+            Err(_) => Err(warp::reject::custom(SyncError::Timeout { session_id: id })),
+        },
+        _ = tokio::time::sleep(WAIT_TIMEOUT) => {
+            // If the barrier never completed in time, remove the stale entry from the
+            // state so a later caller starts a fresh session instead of joining a dead one.
+            let mut state_lock = state.write().await;
+            if let Some(session) = state_lock.get(&id) {
+                if session.arrived < session.expected {
+                    state_lock.remove(&id);
+                }
+            }
+            Err(warp::reject::custom(SyncError::Timeout { session_id: id }))
+        }
     }
+}
 
-    // Wait for the second party to arrive or time out.
-    match tokio::time::timeout(std::time::Duration::from_secs(10), rx).await {
-        Ok(_) => Ok(warp::reply::with_status("Both parties synced.", warp::http::StatusCode::OK)),
-        Err(_) => {
-            // If the second party doesn't arrive in time, remove the timed-out entry from the state.
+fn barrier_synced_reply() -> warp::reply::WithStatus<&'static str> {
+    warp::reply::with_status("All parties synced.", warp::http::StatusCode::OK)
+}
+
+// Background task that periodically evicts stale barrier sessions, so neither an abandoned
+// barrier nor a completed one marked for the straggler grace period leaks a HashMap entry
+// forever: sessions that never completed are evicted after `SESSION_TTL`, and completed
+// ones are evicted after the shorter `COMPLETED_SESSION_RETENTION` -- both measured from
+// `created_at` / `completed_at` but only checked when a sweep runs, so the real worst-case
+// bound on either is the respective constant plus `GC_INTERVAL`.
+fn spawn_gc_task(state: SyncState) {
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(GC_INTERVAL).await;
             let mut state_lock = state.write().await;
-            state_lock.remove(&id);
-            Err(warp::reject::custom(TimeoutError)) // Return a timeout error.
+            evict_stale_sessions(&mut state_lock);
         }
-    }
+    });
 }
 
-// Custom error type for handling timeouts.
+// One GC sweep: drops completed sessions older than `COMPLETED_SESSION_RETENTION` and
+// never-completed sessions older than `SESSION_TTL`.
+fn evict_stale_sessions(sessions: &mut HashMap<String, BarrierSession>) {
+    sessions.retain(|_, session| match session.completed_at {
+        Some(completed_at) => completed_at.elapsed() < COMPLETED_SESSION_RETENTION,
+        None => session.created_at.elapsed() < SESSION_TTL,
+    });
+}
+
+// Error variants surfaced as warp rejections by the barrier handler, each carrying enough
+// context for `handle_rejection` to report exactly why a request was rejected.
 #[derive(Debug)]
-struct TimeoutError;
+enum SyncError {
+    Timeout { session_id: String },
+    SessionFull { session_id: String },
+    InvalidPartyCount { session_id: String, detail: String },
+}
 
-// Implementing the Reject trait for the custom TimeoutError to allow it to be returned as a warp rejection.
-impl warp::reject::Reject for TimeoutError {}
+// Implementing the Reject trait for SyncError to allow it to be returned as a warp rejection.
+impl warp::reject::Reject for SyncError {}
+
+// The structured JSON body returned for every rejection, machine-readable so clients don't
+// have to parse a plaintext message to know what went wrong.
+#[derive(Debug, Serialize, serde::Deserialize)]
+struct ErrorResponse {
+    error: String,
+    detail: String,
+    session_id: String,
+}
+
+// `recover` filter that turns a `SyncError` (or an unhandled warp rejection) into a
+// structured JSON error response with an appropriate HTTP status.
+async fn handle_rejection(err: warp::Rejection) -> Result<impl warp::Reply, Infallible> {
+    let (status, error, detail, session_id) = if let Some(sync_err) = err.find::<SyncError>() {
+        match sync_err {
+            SyncError::Timeout { session_id } => (
+                StatusCode::GATEWAY_TIMEOUT,
+                "timeout",
+                "the barrier did not complete before the wait timeout elapsed".to_string(),
+                session_id.clone(),
+            ),
+            SyncError::SessionFull { session_id } => (
+                StatusCode::CONFLICT,
+                "session_full",
+                "this barrier session has already reached its expected party count".to_string(),
+                session_id.clone(),
+            ),
+            SyncError::InvalidPartyCount { session_id, detail } => (
+                StatusCode::BAD_REQUEST,
+                "invalid_party_count",
+                detail.clone(),
+                session_id.clone(),
+            ),
+        }
+    } else if err.is_not_found() {
+        (
+            StatusCode::NOT_FOUND,
+            "not_found",
+            "no such route".to_string(),
+            String::new(),
+        )
+    } else {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "internal_error",
+            "unhandled rejection".to_string(),
+            String::new(),
+        )
+    };
+
+    let body = ErrorResponse {
+        error: error.to_string(),
+        detail,
+        session_id,
+    };
+    Ok(warp::reply::with_status(warp::reply::json(&body), status))
+}
 
 // Main entry point for the application, defining the Warp server and routes.
 #[tokio::main]
@@ -56,16 +245,21 @@ async fn main() {
     // Create a shared state that will be passed around (it holds the sync data).
     let state: SyncState = Arc::new(RwLock::new(HashMap::new()));
 
+    // Start the background GC sweep for abandoned sessions.
+    spawn_gc_task(state.clone());
+
     // Clone the state for use in the route handler.
     let sync_state = warp::any().map(move || state.clone());
 
-    // Define the route for waiting for a second party. It accepts a unique ID as a parameter.
-    let wait_route = warp::path!("wait-for-second-party" / String)
+    // Define the route for joining an N-party barrier: the id and the expected party count
+    // are both path parameters.
+    let barrier_route = warp::path!("barrier" / String / usize)
         .and(sync_state) // Attach the state to the route.
-        .and_then(wait_for_second_party_handler); // Link the handler to the route.
+        .and_then(wait_for_barrier_handler) // Link the handler to the route.
+        .recover(handle_rejection); // Map rejections to structured JSON error responses.
 
     // Start the server on localhost (127.0.0.1) on port 3030.
-    warp::serve(wait_route).run(([127, 0, 0, 1], 3030)).await;
+    warp::serve(barrier_route).run(([127, 0, 0, 1], 3030)).await;
 }
 
 // Unit tests to verify different scenarios.
@@ -75,9 +269,9 @@ mod tests {
     use warp::http::StatusCode;
     use warp::Reply;
 
-    // Test case where both parties sync successfully.
+    // Test case where two parties sync successfully against an n=2 barrier.
     #[tokio::test]
-    async fn test_both_parties_sync_successfully() {
+    async fn test_two_parties_sync_successfully() {
         let state: SyncState = Arc::new(RwLock::new(HashMap::new()));
         let id = Arc::new("test_id".to_string());
 
@@ -85,14 +279,14 @@ mod tests {
         let state_clone = state.clone();
         let id_clone = id.clone();
         let party_one = tokio::spawn(async move {
-            wait_for_second_party_handler(id_clone.to_string(), state_clone).await
+            wait_for_barrier_handler(id_clone.to_string(), 2, state_clone).await
         });
 
         // Simulate the second party calling the handler.
         let state_clone = state.clone();
         let id_clone = id.clone();
         let party_two = tokio::spawn(async move {
-            wait_for_second_party_handler(id_clone.to_string(), state_clone).await
+            wait_for_barrier_handler(id_clone.to_string(), 2, state_clone).await
         });
 
         // Await both parties and check that they both sync successfully.
@@ -104,52 +298,154 @@ mod tests {
         assert_eq!(res_two.status(), StatusCode::OK);
     }
 
-    // Test case where one party times out due to the absence of the second party.
+    // Test case for a three-party barrier, where all three must arrive before release.
+    #[tokio::test]
+    async fn test_three_party_barrier_releases_together() {
+        let state: SyncState = Arc::new(RwLock::new(HashMap::new()));
+        let id = Arc::new("three_party".to_string());
+
+        let mut handles = Vec::new();
+        for _ in 0..3 {
+            let state_clone = state.clone();
+            let id_clone = id.clone();
+            handles.push(tokio::spawn(async move {
+                wait_for_barrier_handler(id_clone.to_string(), 3, state_clone).await
+            }));
+        }
+
+        for handle in handles {
+            let res = handle.await.unwrap().unwrap().into_response();
+            assert_eq!(res.status(), StatusCode::OK);
+        }
+    }
+
+    // Test case where one party times out due to the rest of the barrier never arriving.
     #[tokio::test]
-    async fn test_timeout_for_single_party() {
+    async fn test_timeout_for_incomplete_barrier() {
         let state: SyncState = Arc::new(RwLock::new(HashMap::new()));
         let id = "test_timeout".to_string();
 
-        // Call the handler for the first party, but there will be no second party.
-        let result = wait_for_second_party_handler(id.clone(), state.clone()).await;
+        // Call the handler for the first party of an n=2 barrier, but no second party
+        // ever arrives.
+        let result = wait_for_barrier_handler(id.clone(), 2, state.clone()).await;
 
         // Check that the result is an error due to a timeout.
         assert!(result.is_err(), "Expected timeout error, but got success");
+
+        // The stale session should have been cleaned up by the timeout path.
+        assert!(!state.read().await.contains_key(&id));
+    }
+
+    // Test case for a party count of zero, which is never satisfiable.
+    #[tokio::test]
+    async fn test_zero_party_count_is_rejected() {
+        let state: SyncState = Arc::new(RwLock::new(HashMap::new()));
+        let result = wait_for_barrier_handler("zero".to_string(), 0, state).await;
+        assert!(result.is_err(), "Expected a rejection for n = 0");
+    }
+
+    // The GC sweep should evict sessions whose first arrival is older than the TTL.
+    #[tokio::test]
+    async fn test_gc_evicts_stale_sessions() {
+        let state: SyncState = Arc::new(RwLock::new(HashMap::new()));
+        let (tx, _rx) = broadcast::channel(1);
+        state.write().await.insert(
+            "stale".to_string(),
+            BarrierSession {
+                expected: 2,
+                arrived: 1,
+                release: tx,
+                created_at: Instant::now() - SESSION_TTL - Duration::from_secs(1),
+                completed_at: None,
+            },
+        );
+
+        let mut state_lock = state.write().await;
+        evict_stale_sessions(&mut state_lock);
+        drop(state_lock);
+
+        assert!(!state.read().await.contains_key("stale"));
     }
 
-    // Test case for edge cases where multiple requests with the same ID are made.
+    // A mismatched party count for an in-progress session should be reported as
+    // `invalid_party_count` with HTTP 400, not a generic rejection.
     #[tokio::test]
-    async fn test_edge_case_multiple_requests_same_id() {
+    async fn test_mismatched_party_count_maps_to_bad_request() {
         let state: SyncState = Arc::new(RwLock::new(HashMap::new()));
-        let id = Arc::new("duplicate_id".to_string());
+        let id = "mismatched".to_string();
 
-        // Simulate three parties trying to sync with the same ID.
         let state_clone = state.clone();
         let id_clone = id.clone();
-        let party_one = tokio::spawn(async move {
-            wait_for_second_party_handler(id_clone.to_string(), state_clone).await
+        tokio::spawn(async move {
+            wait_for_barrier_handler(id_clone, 2, state_clone).await
         });
 
+        // Give the first party a moment to register its session.
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        let result = wait_for_barrier_handler(id.clone(), 3, state.clone()).await;
+        let rejection = match result {
+            Err(rejection) => rejection,
+            Ok(_) => panic!("Expected a mismatched-party-count rejection"),
+        };
+
+        let response = handle_rejection(rejection).await.unwrap().into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    // A real latecomer -- a third party calling an id/n whose barrier already completed --
+    // should be rejected as `session_full` (HTTP 409) rather than silently starting a new
+    // barrier session under the same id.
+    #[tokio::test]
+    async fn test_late_arrival_after_completion_is_session_full() {
+        let state: SyncState = Arc::new(RwLock::new(HashMap::new()));
+        let id = Arc::new("late_arrival".to_string());
+
         let state_clone = state.clone();
         let id_clone = id.clone();
-        let party_two = tokio::spawn(async move {
-            wait_for_second_party_handler(id_clone.to_string(), state_clone).await
+        let party_one = tokio::spawn(async move {
+            wait_for_barrier_handler(id_clone.to_string(), 2, state_clone).await
         });
 
         let state_clone = state.clone();
         let id_clone = id.clone();
-        let party_three = tokio::spawn(async move {
-            wait_for_second_party_handler(id_clone.to_string(), state_clone).await
+        let party_two = tokio::spawn(async move {
+            wait_for_barrier_handler(id_clone.to_string(), 2, state_clone).await
         });
 
-        // Await the responses for all three parties.
-        let res_one = party_one.await.unwrap().unwrap().into_response();
-        let res_two = party_two.await.unwrap().unwrap().into_response();
-        let res_three = party_three.await.unwrap();
+        party_one.await.unwrap().unwrap();
+        party_two.await.unwrap().unwrap();
 
-        // Assert that the first two parties successfully sync, but the third party encounters an error.
-        assert_eq!(res_one.status(), StatusCode::OK);
-        assert_eq!(res_two.status(), StatusCode::OK);
-        assert!(res_three.is_err());
+        // The barrier has released, but the completed session is still retained for the
+        // straggler grace period; a third caller on the same id/n should hit it.
+        let result = wait_for_barrier_handler(id.to_string(), 2, state.clone()).await;
+        let rejection = match result {
+            Err(rejection) => rejection,
+            Ok(_) => panic!("Expected a session_full rejection for the late arrival"),
+        };
+
+        let response = handle_rejection(rejection).await.unwrap().into_response();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+    }
+
+    // A timeout rejection should be recovered into a structured JSON body with HTTP 504.
+    #[tokio::test]
+    async fn test_recover_maps_timeout_to_structured_json() {
+        let state: SyncState = Arc::new(RwLock::new(HashMap::new()));
+        let id = "timeout_json".to_string();
+
+        let result = wait_for_barrier_handler(id.clone(), 2, state).await;
+        let rejection = match result {
+            Err(rejection) => rejection,
+            Ok(_) => panic!("Expected a timeout rejection"),
+        };
+
+        let response = handle_rejection(rejection).await.unwrap().into_response();
+        assert_eq!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+
+        let body = warp::hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let parsed: ErrorResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(parsed.error, "timeout");
+        assert_eq!(parsed.session_id, id);
     }
 }
@@ -1,7 +1,33 @@
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
 use js_sys::Promise;
+use k256::elliptic_curve::{group::GroupEncoding, PrimeField};
+use k256::{ProjectivePoint, Scalar};
+use rand::{thread_rng, RngCore};
+use sha2::{Digest, Sha256};
 use wasm_bindgen::prelude::*;
 use web_sys::wasm_bindgen::JsCast;
 use web_sys::{ErrorEvent, MessageEvent, WebSocket};
+use zk_ni_proof::{generate_random_scalar, DLogProof};
+
+// Party id this client always identifies itself as in the STS handshake; the peer on the
+// other end of `ws_secure_ping` is expected to use a different one.
+const SECURE_PING_CLIENT_PID: u64 = 0;
+
+// Reconnect backoff schedule: start at 250ms, double on every failed attempt, cap at 30s.
+const INITIAL_BACKOFF_MS: i32 = 250;
+const MAX_BACKOFF_MS: i32 = 30_000;
+
+// Keepalive cadence: ping every 15s, and treat a missing pong within 5s as a dead connection.
+const KEEPALIVE_INTERVAL_MS: i32 = 15_000;
+const PONG_DEADLINE_MS: i32 = 5_000;
+
+const KEEPALIVE_PING_FRAME: &str = "__ws_client_ping__";
+const KEEPALIVE_PONG_FRAME: &str = "__ws_client_pong__";
 
 // Macros for logging to the console
 macro_rules! console_log {
@@ -91,3 +117,652 @@ pub async fn ws_ping(endpoint: &str, message: &str) -> Promise {
         onopen_callback.forget();
     })
 }
+
+// Shared state behind a `WsClient`, kept in an `Rc<RefCell<..>>` so the onopen/onmessage/
+// onerror/onclose closures and the setTimeout-driven reconnect/keepalive loops can all see
+// the same connection.
+struct WsClientState {
+    endpoint: String,
+    ws: RefCell<WebSocket>,
+    backoff_ms: Cell<i32>,
+    awaiting_pong: Cell<bool>,
+    // Requests queued via `send`. The front entry is the one currently in flight (sent, and
+    // awaiting either a reply or a reconnect to re-send it); replies are matched to the
+    // front entry in strict send order, since the wire protocol carries no request id to
+    // match them any other way. Later entries are sent as earlier ones are resolved, so
+    // concurrent `send` calls queue instead of clobbering each other's promises.
+    in_flight: RefCell<VecDeque<(String, js_sys::Function, js_sys::Function)>>,
+    // Bumped by `WsClient::close`/`Drop`. The reconnect/keepalive/pong-deadline loops each
+    // capture the generation in effect when they were scheduled and check it again when
+    // their timer fires; a mismatch means the client was closed in the meantime, so they
+    // exit instead of touching the (possibly already-replaced) socket or rescheduling
+    // themselves. This is what lets a closed `WsClient` actually stop running instead of
+    // leaking a live timer loop for the lifetime of the page.
+    generation: Cell<u64>,
+}
+
+/// A persistent WebSocket wrapper that keeps the connection alive: it reconnects
+/// automatically on close or error using exponential backoff with jitter, re-sends any
+/// in-flight request after reconnecting, and treats a missing keepalive pong as a dead
+/// connection that triggers the same reconnect path. Call `close()` (or simply drop it) to
+/// stop the keepalive/reconnect loop and release the underlying socket.
+#[wasm_bindgen]
+pub struct WsClient {
+    state: Rc<WsClientState>,
+}
+
+#[wasm_bindgen]
+impl WsClient {
+    /// Opens the underlying WebSocket to `endpoint` and starts the keepalive loop. Rejects
+    /// with a `JsValue` error (rather than panicking) on a malformed `endpoint`, matching
+    /// how `ws_ping` handles the same `WebSocket::new` failure.
+    #[wasm_bindgen(constructor)]
+    pub fn new(endpoint: &str) -> Result<WsClient, JsValue> {
+        let ws = WebSocket::new(endpoint).map_err(|_| {
+            console_err!("Rust: Failed to create WebSocket");
+            JsValue::from("Rust: Failed to create WebSocket")
+        })?;
+        let state = Rc::new(WsClientState {
+            endpoint: endpoint.to_string(),
+            ws: RefCell::new(ws),
+            backoff_ms: Cell::new(INITIAL_BACKOFF_MS),
+            awaiting_pong: Cell::new(false),
+            in_flight: RefCell::new(VecDeque::new()),
+            generation: Cell::new(0),
+        });
+
+        wire_socket(&state);
+        schedule_keepalive(&state);
+
+        Ok(WsClient { state })
+    }
+
+    /// Stops the keepalive/reconnect loop and closes the underlying socket. The `WsClient`
+    /// is inert after this: any reconnect, keepalive, or pong-deadline timer already
+    /// scheduled will see a stale generation when it fires and return immediately instead
+    /// of touching the socket or scheduling another timer, so the loop that would otherwise
+    /// run for the lifetime of the page winds down instead.
+    pub fn close(&self) {
+        self.state.generation.set(self.state.generation.get() + 1);
+        let ws = self.state.ws.borrow();
+        ws.set_onopen(None);
+        ws.set_onmessage(None);
+        ws.set_onerror(None);
+        ws.set_onclose(None);
+        let _ = ws.close();
+    }
+
+    /// Sends `message` and resolves with the first reply, transparently surviving any
+    /// reconnects that happen while the request is in flight. Safe to call concurrently:
+    /// requests are queued and sent in order, one at a time, so a later call never clobbers
+    /// an earlier one's promise.
+    pub fn send(&self, message: &str) -> Promise {
+        let state = self.state.clone();
+        let message = message.to_string();
+
+        Promise::new(&mut move |resolve, reject| {
+            let mut in_flight = state.in_flight.borrow_mut();
+            let was_idle = in_flight.is_empty();
+            in_flight.push_back((message.clone(), resolve.clone(), reject.clone()));
+            drop(in_flight);
+
+            // Only the front of the queue is ever sent eagerly; a non-empty queue means a
+            // request is already in flight and this one will be sent once it resolves.
+            if was_idle {
+                send_or_reject(&state, &message, &reject);
+            }
+        })
+    }
+}
+
+impl Drop for WsClient {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+// (Re)wires the onopen/onmessage/onerror/onclose handlers for the socket currently held in
+// `state.ws`. Called on construction and again after every reconnect.
+fn wire_socket(state: &Rc<WsClientState>) {
+    let ws = state.ws.borrow().clone();
+
+    let onmessage_state = state.clone();
+    let onmessage_callback = Closure::wrap(Box::new(move |evt: MessageEvent| {
+        let Some(txt) = evt.data().as_string() else {
+            console_err!("Rust: Received non-text message");
+            return;
+        };
+
+        if txt == KEEPALIVE_PONG_FRAME {
+            onmessage_state.awaiting_pong.set(false);
+            return;
+        }
+
+        console_log!("Rust: Received message: {}", txt);
+        let mut in_flight = onmessage_state.in_flight.borrow_mut();
+        if let Some((_, resolve, _)) = in_flight.pop_front() {
+            resolve
+                .call1(&JsValue::NULL, &JsValue::from(txt))
+                .expect("Rust: Failed to resolve promise");
+        }
+        // The reply just resolved the front entry; send the next queued request, if any.
+        if let Some((message, _, reject)) = in_flight.front().cloned() {
+            drop(in_flight);
+            send_or_reject(&onmessage_state, &message, &reject);
+        }
+    }) as Box<dyn FnMut(MessageEvent)>);
+    ws.set_onmessage(Some(onmessage_callback.as_ref().unchecked_ref()));
+    onmessage_callback.forget();
+
+    let onerror_callback = Closure::wrap(Box::new(move |ev: ErrorEvent| {
+        console_err!("Rust: WebSocket error: {}", ev.message());
+    }) as Box<dyn FnMut(ErrorEvent)>);
+    ws.set_onerror(Some(onerror_callback.as_ref().unchecked_ref()));
+    onerror_callback.forget();
+
+    let onclose_state = state.clone();
+    let onclose_callback = Closure::wrap(Box::new(move || {
+        console_err!("Rust: WebSocket closed, scheduling reconnect");
+        schedule_reconnect(&onclose_state);
+    }) as Box<dyn FnMut()>);
+    ws.set_onclose(Some(onclose_callback.as_ref().unchecked_ref()));
+    onclose_callback.forget();
+
+    let onopen_state = state.clone();
+    let onopen_callback = Closure::wrap(Box::new(move || {
+        console_log!("Rust: WebSocket connection (re)opened");
+        onopen_state.backoff_ms.set(INITIAL_BACKOFF_MS);
+        onopen_state.awaiting_pong.set(false);
+
+        // Re-send whatever request was in flight (the queue's front entry) when the
+        // connection dropped.
+        if let Some((message, _, reject)) = onopen_state.in_flight.borrow().front() {
+            send_or_reject(&onopen_state, message, reject);
+        }
+    }) as Box<dyn FnMut()>);
+    ws.set_onopen(Some(onopen_callback.as_ref().unchecked_ref()));
+    onopen_callback.forget();
+}
+
+// Sends `message` on the current socket if it is open. If it is not open this does nothing
+// and the request stays queued; the onopen handler sends it once the socket (re)opens. The
+// in-flight promise is rejected only if `send_with_str` itself fails.
+fn send_or_reject(state: &Rc<WsClientState>, message: &str, reject: &js_sys::Function) {
+    let ws = state.ws.borrow();
+    if ws.ready_state() != WebSocket::OPEN {
+        return;
+    }
+    if let Err(err) = ws.send_with_str(message) {
+        console_err!("Rust: Failed to send message: {:?}", err);
+        reject
+            .call1(&JsValue::NULL, &JsValue::from("Rust: Failed to send message"))
+            .expect("Rust: Failed to reject promise");
+    }
+}
+
+// Tears down the dead socket and opens a fresh one after a jittered backoff delay,
+// doubling the backoff (capped at `MAX_BACKOFF_MS`) for next time. The jitter is full jitter
+// over `[0, delay]`, which avoids synchronized reconnect storms across many clients backing
+// off on the same schedule. No-ops if the client was closed before the timer fires.
+fn schedule_reconnect(state: &Rc<WsClientState>) {
+    let delay = state.backoff_ms.get();
+    state.backoff_ms.set((delay * 2).min(MAX_BACKOFF_MS));
+    let jittered_delay = (thread_rng().next_u32() % (delay as u32 + 1)) as i32;
+
+    let generation = state.generation.get();
+    let state = state.clone();
+    let reconnect = Closure::once(move || {
+        if state.generation.get() != generation {
+            return;
+        }
+        console_log!("Rust: Reconnecting to {}", state.endpoint);
+        match WebSocket::new(&state.endpoint) {
+            Ok(ws) => {
+                *state.ws.borrow_mut() = ws;
+                wire_socket(&state);
+            }
+            Err(_) => {
+                console_err!("Rust: Reconnect attempt failed to create WebSocket");
+                schedule_reconnect(&state);
+            }
+        }
+    });
+
+    let window = web_sys::window().expect("Rust: no global window");
+    window
+        .set_timeout_with_callback_and_timeout_and_arguments_0(
+            reconnect.as_ref().unchecked_ref(),
+            jittered_delay,
+        )
+        .expect("Rust: Failed to schedule reconnect");
+    reconnect.forget();
+}
+
+// Sends a keepalive ping on `KEEPALIVE_INTERVAL_MS` and arms a deadline check that treats a
+// missing pong as a dead connection, triggering the same reconnect path as a close/error.
+// Stops rescheduling itself once the client is closed, so the loop doesn't outlive it.
+fn schedule_keepalive(state: &Rc<WsClientState>) {
+    let generation = state.generation.get();
+    let state_for_ping = state.clone();
+    let ping = Closure::wrap(Box::new(move || {
+        if state_for_ping.generation.get() != generation {
+            return;
+        }
+        let ws = state_for_ping.ws.borrow();
+        if ws.ready_state() == WebSocket::OPEN {
+            state_for_ping.awaiting_pong.set(true);
+            let _ = ws.send_with_str(KEEPALIVE_PING_FRAME);
+            drop(ws);
+            schedule_pong_deadline(&state_for_ping);
+        }
+        schedule_keepalive(&state_for_ping);
+    }) as Box<dyn FnMut()>);
+
+    let window = web_sys::window().expect("Rust: no global window");
+    window
+        .set_timeout_with_callback_and_timeout_and_arguments_0(
+            ping.as_ref().unchecked_ref(),
+            KEEPALIVE_INTERVAL_MS,
+        )
+        .expect("Rust: Failed to schedule keepalive ping");
+    ping.forget();
+}
+
+// Checks, after `PONG_DEADLINE_MS`, whether the most recent keepalive ping ever got a pong
+// back; if not, the connection is considered dead and a reconnect is triggered. No-ops if
+// the client was closed before the deadline fires.
+fn schedule_pong_deadline(state: &Rc<WsClientState>) {
+    let generation = state.generation.get();
+    let state = state.clone();
+    let deadline = Closure::once(move || {
+        if state.generation.get() != generation {
+            return;
+        }
+        if state.awaiting_pong.get() {
+            console_err!("Rust: Missed keepalive pong, treating connection as dead");
+            state.awaiting_pong.set(false);
+            let _ = state.ws.borrow().close();
+            schedule_reconnect(&state);
+        }
+    });
+
+    let window = web_sys::window().expect("Rust: no global window");
+    window
+        .set_timeout_with_callback_and_timeout_and_arguments_0(
+            deadline.as_ref().unchecked_ref(),
+            PONG_DEADLINE_MS,
+        )
+        .expect("Rust: Failed to schedule pong deadline check");
+    deadline.forget();
+}
+
+// Encodes this side's ephemeral point as `POINT|hex(E)`. Sent before either side has seen
+// the other's point, so it can't carry a proof yet (there is nothing to bind one to).
+fn encode_point_frame(e_point: ProjectivePoint) -> String {
+    format!("POINT|{}", hex::encode(e_point.to_bytes()))
+}
+
+fn decode_point_frame(frame: &str) -> Option<ProjectivePoint> {
+    decode_point(frame.strip_prefix("POINT|")?)
+}
+
+// Encodes this side's handshake proof as `PROOF|pid|hex(t)|hex(s)`, sent only once both
+// ephemeral points are known so the proof can be bound to the pair of them (see
+// `transcript_binding`). There's no serde dependency elsewhere in this crate, so the wire
+// format here is a plain delimited string rather than JSON.
+fn encode_proof_frame(pid: u64, proof: &DLogProof) -> String {
+    format!(
+        "PROOF|{}|{}|{}",
+        pid,
+        hex::encode(proof.t.to_bytes()),
+        hex::encode(proof.s.to_bytes()),
+    )
+}
+
+fn decode_proof_frame(frame: &str) -> Option<(u64, DLogProof)> {
+    let rest = frame.strip_prefix("PROOF|")?;
+    let mut parts = rest.splitn(3, '|');
+    let pid: u64 = parts.next()?.parse().ok()?;
+    let t = decode_point(parts.next()?)?;
+    let s = decode_scalar(parts.next()?)?;
+    Some((pid, DLogProof { t, s }))
+}
+
+// Binds a handshake proof to the session id *and* both parties' ephemeral points, so a
+// proof is only valid for this specific pair of ephemeral contributions and can't be
+// replayed against a different peer point or a different connection. The two points are
+// ordered independently of which side is "self" vs. "peer" so both ends compute the same
+// string.
+fn transcript_binding(sid: &str, point_a: ProjectivePoint, point_b: ProjectivePoint) -> String {
+    let hex_a = hex::encode(point_a.to_bytes());
+    let hex_b = hex::encode(point_b.to_bytes());
+    let (first, second) = if hex_a <= hex_b { (hex_a, hex_b) } else { (hex_b, hex_a) };
+    format!("{}:{}:{}", sid, first, second)
+}
+
+fn decode_point(hex_str: &str) -> Option<ProjectivePoint> {
+    let bytes = hex::decode(hex_str).ok()?;
+    let encoded = k256::CompressedPoint::from_exact_iter(bytes.into_iter())?;
+    Option::from(ProjectivePoint::from_bytes(&encoded))
+}
+
+fn decode_scalar(hex_str: &str) -> Option<Scalar> {
+    let bytes = hex::decode(hex_str).ok()?;
+    let array: [u8; 32] = bytes.try_into().ok()?;
+    Option::from(Scalar::from_repr(array.into()))
+}
+
+// Derives the AEAD key for an authenticated channel from the STS shared point: the shared
+// secret `e_self * E_peer` hashed through SHA-256.
+fn derive_channel_key(shared_point: ProjectivePoint) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(shared_point.to_bytes());
+    hasher.finalize().into()
+}
+
+// Encrypts `plaintext` under `key` with a fresh random nonce, wiring the two together as
+// `hex(nonce):hex(ciphertext)` so a single text frame carries everything the peer needs.
+fn encrypt_frame(key: &[u8; 32], plaintext: &str) -> String {
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    let mut nonce_bytes = [0u8; 12];
+    thread_rng().fill_bytes(&mut nonce_bytes);
+    let nonce = Nonce::from_slice(&nonce_bytes);
+
+    let ciphertext = cipher
+        .encrypt(nonce, plaintext.as_bytes())
+        .expect("Rust: AEAD encryption failed");
+    format!("{}:{}", hex::encode(nonce_bytes), hex::encode(ciphertext))
+}
+
+// Decrypts a frame produced by `encrypt_frame`, returning `None` if the frame is malformed
+// or authentication fails.
+fn decrypt_frame(key: &[u8; 32], frame: &str) -> Option<String> {
+    let (nonce_hex, ciphertext_hex) = frame.split_once(':')?;
+    let nonce_bytes = hex::decode(nonce_hex).ok()?;
+    let ciphertext = hex::decode(ciphertext_hex).ok()?;
+
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    let nonce = Nonce::from_slice(&nonce_bytes);
+    let plaintext = cipher.decrypt(nonce, ciphertext.as_slice()).ok()?;
+    String::from_utf8(plaintext).ok()
+}
+
+// Where a `ws_secure_ping` connection is in the handshake. The proof exchanged in
+// `AwaitingPeerProof` is bound to *both* ephemeral points (see `transcript_binding`), which
+// is only possible once each side has seen the other's `POINT` frame - hence the two-step
+// point-then-proof exchange instead of sending a proof alongside the point up front.
+#[derive(Clone, Copy)]
+enum HandshakeStage {
+    AwaitingPeerPoint,
+    AwaitingPeerProof { peer_point: ProjectivePoint },
+    Complete,
+}
+
+// Shared state for a `ws_secure_ping` connection: the STS session id this connection is
+// bound to (so a captured proof can't be replayed on another connection), this side's
+// ephemeral secret/point, the handshake's progress, and the symmetric key once it completes.
+struct SecureChannelState {
+    sid: String,
+    ephemeral_secret: Scalar,
+    ephemeral_point: ProjectivePoint,
+    stage: RefCell<HandshakeStage>,
+    channel_key: RefCell<Option<[u8; 32]>>,
+}
+
+/// Establishes a WebSocket connection authenticated and encrypted with a Station-to-Station
+/// handshake over secp256k1: both sides exchange ephemeral points, then each proves
+/// knowledge of its ephemeral scalar with a `DLogProof` bound to this connection's session
+/// id *and* both ephemeral points (`transcript_binding`) - so a proof only authenticates the
+/// specific peer that contributed the other point in this exchange, not just "someone who
+/// knows some discrete log". The shared point is hashed into a symmetric key and `message`
+/// is sent as the first AEAD-encrypted application frame. This is the opt-in
+/// authenticated/encrypted counterpart to the plaintext `ws_ping`.
+///
+/// Note: like plain STS, this authenticates the two ephemeral contributions to each other
+/// but not to any long-term identity, so it doesn't by itself rule out an active attacker
+/// running independent handshakes with each side (unknown key-share). Binding to a
+/// long-term identity key would close that gap but is out of scope here.
+#[wasm_bindgen]
+pub async fn ws_secure_ping(endpoint: &str, message: &str) -> Promise {
+    let base_point = ProjectivePoint::GENERATOR;
+    let mut sid_bytes = [0u8; 16];
+    thread_rng().fill_bytes(&mut sid_bytes);
+
+    let ephemeral_secret = generate_random_scalar();
+    let state = Rc::new(SecureChannelState {
+        sid: hex::encode(sid_bytes),
+        ephemeral_secret,
+        ephemeral_point: base_point * ephemeral_secret,
+        stage: RefCell::new(HandshakeStage::AwaitingPeerPoint),
+        channel_key: RefCell::new(None),
+    });
+
+    Promise::new(&mut move |resolve, reject| {
+        console_log!("Rust: Connecting (secure) to {}", endpoint);
+
+        let ws = match WebSocket::new(endpoint) {
+            Ok(ws) => ws,
+            Err(_) => {
+                console_err!("Rust: Failed to create WebSocket");
+                reject
+                    .call1(&JsValue::NULL, &JsValue::from("Rust: Failed to create WebSocket"))
+                    .expect("Rust: Failed to reject promise");
+                return;
+            }
+        };
+
+        let resolve_clone = resolve.clone();
+        let reject_clone = reject.clone();
+        let onmessage_state = state.clone();
+        let message_to_send = message.to_string();
+        let ws_for_onmessage = ws.clone();
+        let onmessage_callback = Closure::wrap(Box::new(move |evt: MessageEvent| {
+            let Some(txt) = evt.data().as_string() else {
+                console_err!("Rust: Received non-text message");
+                reject_clone
+                    .call1(&JsValue::NULL, &JsValue::from("Rust: Received non-text message"))
+                    .expect("Rust: Failed to reject promise");
+                return;
+            };
+
+            let stage = *onmessage_state.stage.borrow();
+            match stage {
+                HandshakeStage::AwaitingPeerPoint => {
+                    let Some(peer_point) = decode_point_frame(&txt) else {
+                        console_err!("Rust: Malformed handshake point frame");
+                        reject_clone
+                            .call1(&JsValue::NULL, &JsValue::from("Rust: Malformed handshake point frame"))
+                            .expect("Rust: Failed to reject promise");
+                        return;
+                    };
+
+                    // Only now that both ephemeral points are known can the proof be bound
+                    // to both of them, so this is the first moment we can produce it.
+                    let binding = transcript_binding(&onmessage_state.sid, onmessage_state.ephemeral_point, peer_point);
+                    let proof = DLogProof::prove(
+                        &binding,
+                        SECURE_PING_CLIENT_PID,
+                        onmessage_state.ephemeral_secret,
+                        onmessage_state.ephemeral_point,
+                        base_point,
+                    );
+                    *onmessage_state.stage.borrow_mut() = HandshakeStage::AwaitingPeerProof { peer_point };
+
+                    let frame = encode_proof_frame(SECURE_PING_CLIENT_PID, &proof);
+                    if let Err(err) = ws_for_onmessage.send_with_str(&frame) {
+                        console_err!("Rust: Failed to send handshake proof: {:?}", err);
+                        reject_clone
+                            .call1(&JsValue::NULL, &JsValue::from("Rust: Failed to send handshake proof"))
+                            .expect("Rust: Failed to reject promise");
+                    }
+                }
+                HandshakeStage::AwaitingPeerProof { peer_point } => {
+                    let Some((peer_pid, peer_proof)) = decode_proof_frame(&txt) else {
+                        console_err!("Rust: Malformed handshake proof frame");
+                        reject_clone
+                            .call1(&JsValue::NULL, &JsValue::from("Rust: Malformed handshake proof frame"))
+                            .expect("Rust: Failed to reject promise");
+                        return;
+                    };
+
+                    // Bound to both ephemeral points, so this proof only authenticates the
+                    // peer that actually contributed `peer_point` to this exchange.
+                    let binding = transcript_binding(&onmessage_state.sid, onmessage_state.ephemeral_point, peer_point);
+                    if !peer_proof.verify(&binding, peer_pid, peer_point, base_point) {
+                        console_err!("Rust: Peer handshake proof failed verification");
+                        reject_clone
+                            .call1(&JsValue::NULL, &JsValue::from("Rust: Peer handshake proof failed verification"))
+                            .expect("Rust: Failed to reject promise");
+                        return;
+                    }
+
+                    let shared_point = peer_point * onmessage_state.ephemeral_secret;
+                    let key = derive_channel_key(shared_point);
+                    *onmessage_state.channel_key.borrow_mut() = Some(key);
+                    *onmessage_state.stage.borrow_mut() = HandshakeStage::Complete;
+
+                    let frame = encrypt_frame(&key, &message_to_send);
+                    if let Err(err) = ws_for_onmessage.send_with_str(&frame) {
+                        console_err!("Rust: Failed to send encrypted message: {:?}", err);
+                        reject_clone
+                            .call1(&JsValue::NULL, &JsValue::from("Rust: Failed to send encrypted message"))
+                            .expect("Rust: Failed to reject promise");
+                    }
+                }
+                HandshakeStage::Complete => {
+                    let key = onmessage_state.channel_key.borrow().expect("channel key is set");
+                    match decrypt_frame(&key, &txt) {
+                        Some(plaintext) => {
+                            console_log!("Rust: Received secure message: {}", plaintext);
+                            resolve_clone
+                                .call1(&JsValue::NULL, &JsValue::from(plaintext))
+                                .expect("Rust: Failed to resolve promise");
+                        }
+                        None => {
+                            console_err!("Rust: Failed to decrypt message");
+                            reject_clone
+                                .call1(&JsValue::NULL, &JsValue::from("Rust: Failed to decrypt message"))
+                                .expect("Rust: Failed to reject promise");
+                        }
+                    }
+                }
+            }
+        }) as Box<dyn FnMut(MessageEvent)>);
+        ws.set_onmessage(Some(onmessage_callback.as_ref().unchecked_ref()));
+        onmessage_callback.forget();
+
+        let reject_clone = reject.clone();
+        let onerror_callback = Closure::wrap(Box::new(move |ev: ErrorEvent| {
+            console_err!("Rust: WebSocket error: {}", ev.message());
+            reject_clone
+                .call1(&JsValue::NULL, &JsValue::from(ev.message()))
+                .expect("Rust: Failed to reject promise");
+        }) as Box<dyn FnMut(ErrorEvent)>);
+        ws.set_onerror(Some(onerror_callback.as_ref().unchecked_ref()));
+        onerror_callback.forget();
+
+        let reject_clone = reject.clone();
+        let ws_clone = ws.clone();
+        let onopen_state = state.clone();
+        let onopen_callback = Closure::wrap(Box::new(move || {
+            console_log!("Rust: WebSocket connection opened, starting STS handshake");
+            // Only the ephemeral point goes out here - the proof can't be bound to both
+            // sides' points until the peer's point is known, which happens in onmessage.
+            let frame = encode_point_frame(onopen_state.ephemeral_point);
+            if let Err(err) = ws_clone.send_with_str(&frame) {
+                console_err!("Rust: Failed to send handshake point: {:?}", err);
+                reject_clone
+                    .call1(&JsValue::NULL, &JsValue::from("Rust: Failed to send handshake point"))
+                    .expect("Rust: Failed to reject promise");
+            }
+        }) as Box<dyn FnMut()>);
+        ws.set_onopen(Some(onopen_callback.as_ref().unchecked_ref()));
+        onopen_callback.forget();
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_point_frame_round_trip() {
+        let point = ProjectivePoint::GENERATOR * generate_random_scalar();
+        let frame = encode_point_frame(point);
+        assert_eq!(decode_point_frame(&frame), Some(point));
+    }
+
+    #[test]
+    fn test_decode_point_frame_rejects_wrong_prefix() {
+        let point = ProjectivePoint::GENERATOR;
+        let frame = encode_point_frame(point).replace("POINT|", "PROOF|");
+        assert_eq!(decode_point_frame(&frame), None);
+    }
+
+    #[test]
+    fn test_proof_frame_round_trip() {
+        let base_point = ProjectivePoint::GENERATOR;
+        let x = generate_random_scalar();
+        let y = base_point * x;
+        let proof = DLogProof::prove("sid", 7, x, y, base_point);
+
+        let frame = encode_proof_frame(7, &proof);
+        let (pid, decoded) = decode_proof_frame(&frame).expect("frame should decode");
+        assert_eq!(pid, 7);
+        assert!(decoded.verify("sid", 7, y, base_point));
+    }
+
+    #[test]
+    fn test_transcript_binding_is_order_independent() {
+        let a = ProjectivePoint::GENERATOR * generate_random_scalar();
+        let b = ProjectivePoint::GENERATOR * generate_random_scalar();
+        assert_eq!(transcript_binding("sid", a, b), transcript_binding("sid", b, a));
+    }
+
+    #[test]
+    fn test_transcript_binding_depends_on_both_points() {
+        let a = ProjectivePoint::GENERATOR * generate_random_scalar();
+        let b = ProjectivePoint::GENERATOR * generate_random_scalar();
+        let c = ProjectivePoint::GENERATOR * generate_random_scalar();
+        assert_ne!(transcript_binding("sid", a, b), transcript_binding("sid", a, c));
+        assert_ne!(transcript_binding("sid", a, b), transcript_binding("other-sid", a, b));
+    }
+
+    #[test]
+    fn test_derive_channel_key_is_deterministic_and_point_dependent() {
+        let point_a = ProjectivePoint::GENERATOR * generate_random_scalar();
+        let point_b = ProjectivePoint::GENERATOR * generate_random_scalar();
+
+        assert_eq!(derive_channel_key(point_a), derive_channel_key(point_a));
+        assert_ne!(derive_channel_key(point_a), derive_channel_key(point_b));
+    }
+
+    #[test]
+    fn test_encrypt_decrypt_frame_round_trip() {
+        let key = derive_channel_key(ProjectivePoint::GENERATOR * generate_random_scalar());
+        let frame = encrypt_frame(&key, "hello, peer");
+        assert_eq!(decrypt_frame(&key, &frame), Some("hello, peer".to_string()));
+    }
+
+    #[test]
+    fn test_decrypt_frame_rejects_tampered_ciphertext() {
+        let key = derive_channel_key(ProjectivePoint::GENERATOR * generate_random_scalar());
+        let frame = encrypt_frame(&key, "hello, peer");
+
+        let (nonce_hex, ciphertext_hex) = frame.split_once(':').expect("frame has a nonce");
+        let mut ciphertext = hex::decode(ciphertext_hex).expect("valid hex");
+        ciphertext[0] ^= 0xFF;
+        let tampered = format!("{}:{}", nonce_hex, hex::encode(ciphertext));
+
+        assert_eq!(decrypt_frame(&key, &tampered), None);
+    }
+
+    #[test]
+    fn test_decrypt_frame_rejects_wrong_key() {
+        let key = derive_channel_key(ProjectivePoint::GENERATOR * generate_random_scalar());
+        let other_key = derive_channel_key(ProjectivePoint::GENERATOR * generate_random_scalar());
+        let frame = encrypt_frame(&key, "hello, peer");
+
+        assert_eq!(decrypt_frame(&other_key, &frame), None);
+    }
+}
@@ -0,0 +1,415 @@
+// Required imports from the `k256` library for elliptic curve operations
+use k256::{elliptic_curve::{group::GroupEncoding, ops::Reduce}, ProjectivePoint, Scalar, U256};
+
+// Importing random number generation utilities
+use rand::{thread_rng, Rng};
+
+// Importing hashing functionality
+use sha2::{Digest, Sha256};
+
+/// A struct to encapsulate the Schnorr ZK DLOG (Zero-Knowledge Discrete Logarithm) proof.
+/// The proof consists of two components:
+/// 1. `t` - Commitment (a point on the elliptic curve)
+/// 2. `s` - Response (a scalar value derived from the commitment and challenge)
+#[derive(Debug, PartialEq, Eq)]
+pub struct DLogProof {
+    pub t: ProjectivePoint, // Commitment
+    pub s: Scalar,          // Response
+}
+
+impl DLogProof {
+    /// A deterministic hash function to compute the challenge value `c`.
+    /// The challenge depends on:
+    /// - `sid`: Session ID (a string identifier for the proof context)
+    /// - `pid`: Party ID (a unique identifier for the prover)
+    /// - `points`: A list of elliptic curve points used to derive the challenge
+    fn hash_points(sid: &str, pid: u64, points: &[ProjectivePoint]) -> Scalar {
+        let mut hasher = Sha256::new(); // Initialize a SHA-256 hasher
+        hasher.update(sid.as_bytes()); // Include the session ID
+        hasher.update(pid.to_le_bytes()); // Include the party ID in little-endian format
+        for point in points {
+            hasher.update(point.to_bytes().clone()); // Include each point (as bytes)
+        }
+        let hash = hasher.finalize(); // Finalize the hash computation
+        Scalar::reduce(U256::from_be_slice(&hash)) // Reduce the hash to fit in the scalar field
+    }
+
+    /// Method to generate a Schnorr proof that the prover knows the secret `x` such that `y = x * G`.
+    /// - `sid`: Session ID
+    /// - `pid`: Party ID
+    /// - `x`: Secret scalar (private key)
+    /// - `y`: Public key (`x * G`, where `G` is the base point)
+    /// - `base_point`: The base point `G` of the elliptic curve
+    pub fn prove(
+        sid: &str,
+        pid: u64,
+        x: Scalar,
+        y: ProjectivePoint,
+        base_point: ProjectivePoint,
+    ) -> Self {
+        let r = Scalar::generate_vartime(&mut thread_rng()); // Generate a random scalar `r`
+        let t = base_point * r; // Compute commitment `t = r * G`
+        let c = Self::hash_points(sid, pid, &[base_point, y, t]); // Compute challenge `c`
+        let s = r + c * x; // Compute response `s = r + c * x`
+        Self { t, s } // Return the proof containing `t` and `s`
+    }
+
+    /// Method to verify the Schnorr proof
+    /// - `sid`: Session ID
+    /// - `pid`: Party ID
+    /// - `y`: Public key (`x * G`)
+    /// - `base_point`: The base point `G` of the elliptic curve
+    pub fn verify(
+        &self,
+        sid: &str,
+        pid: u64,
+        y: ProjectivePoint,
+        base_point: ProjectivePoint,
+    ) -> bool {
+        let c = Self::hash_points(sid, pid, &[base_point, y, self.t]); // Recompute challenge `c`
+        let lhs = base_point * self.s; // Compute `s * G`
+        let rhs = self.t + (y * c); // Compute `t + c * y`
+        lhs == rhs // Verification succeeds if `s * G == t + c * y`
+    }
+
+    /// Verifies many Schnorr proofs at once using a single randomized aggregate equation.
+    /// The terms are accumulated with separate full-width scalar multiplications (two per
+    /// proof, plus one for `G`), not a multi-scalar multiplication, so this does no less
+    /// curve arithmetic than calling `verify` once per proof.
+    ///
+    /// For each proof `i` this recomputes its challenge `c_i` and draws a fresh random
+    /// 128-bit nonzero scalar `rho_i`, then checks the single combined equation
+    /// `(sum rho_i * s_i) * G == sum rho_i * t_i + sum (rho_i * c_i) * y_i`. If it holds,
+    /// every proof is valid with overwhelming probability. The `rho_i` must be freshly
+    /// random per call and never zero, or a malicious prover could construct proofs whose
+    /// individually-invalid terms cancel in the aggregate.
+    ///
+    /// Returns `true` only if every proof in `proofs` is valid; callers that need to know
+    /// which proof failed can fall back to calling `verify` on each one individually.
+    pub fn verify_batch(proofs: &[(&str, u64, ProjectivePoint, DLogProof)], base_point: ProjectivePoint) -> bool {
+        if proofs.is_empty() {
+            return true;
+        }
+
+        let mut rng = thread_rng();
+        let mut lhs_scalar_sum = Scalar::ZERO;
+        let mut t_sum = ProjectivePoint::IDENTITY;
+        let mut cy_sum = ProjectivePoint::IDENTITY;
+
+        for (sid, pid, y, proof) in proofs.iter() {
+            let c = Self::hash_points(sid, *pid, &[base_point, *y, proof.t]);
+            let rho = random_nonzero_128_scalar(&mut rng);
+
+            lhs_scalar_sum += rho * proof.s;
+            t_sum += proof.t * rho;
+            cy_sum += *y * (rho * c);
+        }
+
+        base_point * lhs_scalar_sum == t_sum + cy_sum
+    }
+}
+
+/// Helper function to generate a random scalar
+pub fn generate_random_scalar() -> Scalar {
+    Scalar::generate_vartime(&mut thread_rng())
+}
+
+/// Draws a random, nonzero 128-bit scalar for use as a batch-verification coefficient.
+/// A short scalar is enough for soundness here; it must never be zero or its term would
+/// drop out of the sum. `verify_batch` multiplies by it at full width all the same.
+fn random_nonzero_128_scalar(rng: &mut impl Rng) -> Scalar {
+    loop {
+        let value: u128 = rng.gen();
+        if value == 0 {
+            continue;
+        }
+        let scalar = Scalar::reduce(U256::from(value));
+        if scalar != Scalar::ZERO {
+            return scalar;
+        }
+    }
+}
+
+/// The round-1 broadcast message a DKG party publishes: its public share `y_i = x_i * G`
+/// together with a `DLogProof` proving knowledge of the secret `x_i` behind it.
+#[derive(Debug)]
+pub struct DkgRound1Message {
+    pub pid: u64,
+    pub y: ProjectivePoint,
+    pub proof: DLogProof,
+}
+
+/// Returned from `DkgParty::round2` when a peer's round-1 proof fails to verify, naming
+/// the offending party so the run can be diagnosed instead of just aborted.
+#[derive(Debug)]
+pub struct DkgVerificationError {
+    pub offending_pid: u64,
+}
+
+/// One party's local state in a distributed key generation run: its own secret share and
+/// the session parameters (`sid`, `pid`, base point) it proves knowledge against.
+pub struct DkgParty {
+    sid: String,
+    pid: u64,
+    base_point: ProjectivePoint,
+    x_i: Scalar,
+}
+
+impl DkgParty {
+    /// Starts a new DKG party for the given session, generating its secret share `x_i`.
+    pub fn new(sid: &str, pid: u64, base_point: ProjectivePoint) -> Self {
+        Self {
+            sid: sid.to_string(),
+            pid,
+            base_point,
+            x_i: generate_random_scalar(),
+        }
+    }
+
+    /// Produces this party's round-1 broadcast message: its public share `y_i = x_i * G`
+    /// together with a `DLogProof` of knowledge of `x_i`.
+    pub fn round1(&self) -> DkgRound1Message {
+        let y_i = self.base_point * self.x_i;
+        let proof = DLogProof::prove(&self.sid, self.pid, self.x_i, y_i, self.base_point);
+        DkgRound1Message {
+            pid: self.pid,
+            y: y_i,
+            proof,
+        }
+    }
+
+    /// Verifies every peer's round-1 message, rejecting the whole run on the first
+    /// failure (reporting the offending `pid`), and returns the combined aggregate public
+    /// key `Y = sum y_i` once every proof checks out.
+    pub fn round2(
+        &self,
+        messages: &[DkgRound1Message],
+    ) -> Result<ProjectivePoint, DkgVerificationError> {
+        let mut y_sum = ProjectivePoint::IDENTITY;
+        for message in messages {
+            if !message
+                .proof
+                .verify(&self.sid, message.pid, message.y, self.base_point)
+            {
+                return Err(DkgVerificationError {
+                    offending_pid: message.pid,
+                });
+            }
+            y_sum += message.y;
+        }
+        Ok(y_sum)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test case to verify a valid Schnorr proof
+    #[test]
+    fn test_valid_proof() {
+        // Test inputs
+        let sid = "test_sid";
+        let pid = 12345;
+
+        // Generate random secret and compute corresponding public key
+        let x = generate_random_scalar();
+        let base_point = ProjectivePoint::GENERATOR;
+        let y = base_point * x;
+
+        // Generate and verify the proof
+        let proof = DLogProof::prove(sid, pid, x, y, base_point);
+        assert!(proof.verify(sid, pid, y, base_point), "Valid proof should pass");
+    }
+
+    /// Test case with an invalid public key `y`
+    #[test]
+    fn test_invalid_proof_wrong_y() {
+        let sid = "test_sid";
+        let pid = 12345;
+
+        // Generate random secret but use a mismatched public key
+        let x = generate_random_scalar();
+        let base_point = ProjectivePoint::GENERATOR;
+        let y = base_point * generate_random_scalar(); // Different scalar
+
+        let proof = DLogProof::prove(sid, pid, x, y, base_point);
+        assert!(
+            !proof.verify(sid, pid, y, base_point),
+            "Proof with incorrect y should fail"
+        );
+    }
+
+    /// Test case with an incorrect session ID
+    #[test]
+    fn test_invalid_proof_wrong_sid() {
+        let sid = "test_sid";
+        let wrong_sid = "wrong_sid";
+        let pid = 12345;
+
+        let x = generate_random_scalar();
+        let base_point = ProjectivePoint::GENERATOR;
+        let y = base_point * x;
+
+        let proof = DLogProof::prove(sid, pid, x, y, base_point);
+        assert!(
+            !proof.verify(wrong_sid, pid, y, base_point),
+            "Proof with incorrect SID should fail"
+        );
+    }
+
+    /// Test case with an incorrect party ID
+    #[test]
+    fn test_invalid_proof_wrong_pid() {
+        let sid = "test_sid";
+        let pid = 12345;
+        let wrong_pid = 54321;
+
+        let x = generate_random_scalar();
+        let base_point = ProjectivePoint::GENERATOR;
+        let y = base_point * x;
+
+        let proof = DLogProof::prove(sid, pid, x, y, base_point);
+        assert!(
+            !proof.verify(sid, wrong_pid, y, base_point),
+            "Proof with incorrect PID should fail"
+        );
+    }
+
+    /// Test case for edge case: secret scalar `x` is zero
+    #[test]
+    fn test_edge_case_zero_scalar() {
+        let sid = "test_sid";
+        let pid = 12345;
+
+        let x = Scalar::ZERO; // Scalar zero
+        let base_point = ProjectivePoint::GENERATOR;
+        let y = base_point * x;
+
+        let proof = DLogProof::prove(sid, pid, x, y, base_point);
+        assert!(proof.verify(sid, pid, y, base_point), "Proof with zero scalar should pass");
+    }
+
+    /// Test case for edge case: maximum scalar value
+    #[test]
+    fn test_edge_case_max_scalar() {
+        let sid = "test_sid";
+        let pid = 12345;
+
+        let max_scalar_bytes = [0xFF; 32]; // Maximum 256-bit scalar value
+        let x = Scalar::reduce(U256::from_be_slice(&max_scalar_bytes)); // Reduce scalar to curve order
+        let base_point = ProjectivePoint::GENERATOR;
+        let y = base_point * x;
+
+        let proof = DLogProof::prove(sid, pid, x, y, base_point);
+        assert!(proof.verify(sid, pid, y, base_point), "Proof with max scalar should pass");
+    }
+
+    /// Test case to measure proof and verification timings
+    #[test]
+    fn test_proof_timing() {
+        let sid = "timing_test";
+        let pid = 67890;
+
+        let x = generate_random_scalar();
+        let base_point = ProjectivePoint::GENERATOR;
+        let y = base_point * x;
+
+        let start_proof = std::time::Instant::now();
+        let proof = DLogProof::prove(sid, pid, x, y, base_point);
+        let proof_time = start_proof.elapsed().as_millis();
+        assert!(proof_time < 500, "Proof computation should be fast");
+
+        let start_verify = std::time::Instant::now();
+        let valid = proof.verify(sid, pid, y, base_point);
+        let verify_time = start_verify.elapsed().as_millis();
+        assert!(verify_time < 500, "Verification computation should be fast");
+        assert!(valid, "Valid proof should pass verification");
+    }
+
+    /// Test case to verify that a batch of valid proofs passes `verify_batch`.
+    #[test]
+    fn test_verify_batch_all_valid() {
+        let base_point = ProjectivePoint::GENERATOR;
+
+        let mut proofs = Vec::new();
+        for (sid, pid) in [("sid_a", 1u64), ("sid_b", 2), ("sid_c", 3)] {
+            let x = generate_random_scalar();
+            let y = base_point * x;
+            let proof = DLogProof::prove(sid, pid, x, y, base_point);
+            proofs.push((sid, pid, y, proof));
+        }
+
+        assert!(DLogProof::verify_batch(&proofs, base_point), "Batch of valid proofs should pass");
+    }
+
+    /// Test case to verify that a single tampered proof fails the whole batch.
+    #[test]
+    fn test_verify_batch_rejects_tampered_proof() {
+        let base_point = ProjectivePoint::GENERATOR;
+
+        let mut proofs = Vec::new();
+        for (sid, pid) in [("sid_a", 1u64), ("sid_b", 2), ("sid_c", 3)] {
+            let x = generate_random_scalar();
+            let y = base_point * x;
+            let proof = DLogProof::prove(sid, pid, x, y, base_point);
+            proofs.push((sid, pid, y, proof));
+        }
+
+        // Corrupt the response scalar of the last proof.
+        let last = proofs.len() - 1;
+        proofs[last].3.s += Scalar::ONE;
+
+        assert!(!DLogProof::verify_batch(&proofs, base_point), "Batch with a tampered proof should fail");
+    }
+
+    /// An empty batch is trivially valid since there is nothing to check.
+    #[test]
+    fn test_verify_batch_empty_is_valid() {
+        let base_point = ProjectivePoint::GENERATOR;
+        let proofs: Vec<(&str, u64, ProjectivePoint, DLogProof)> = Vec::new();
+        assert!(DLogProof::verify_batch(&proofs, base_point));
+    }
+
+    /// Test case for a full three-party DKG run producing the expected aggregate key.
+    #[test]
+    fn test_dkg_three_parties_produce_aggregate_key() {
+        let base_point = ProjectivePoint::GENERATOR;
+        let sid = "dkg_sid";
+
+        let parties: Vec<DkgParty> = (1..=3)
+            .map(|pid| DkgParty::new(sid, pid, base_point))
+            .collect();
+
+        let messages: Vec<DkgRound1Message> = parties.iter().map(|party| party.round1()).collect();
+
+        let expected_y: ProjectivePoint = messages.iter().map(|m| m.y).fold(ProjectivePoint::IDENTITY, |acc, y| acc + y);
+
+        for party in &parties {
+            let y = party.round2(&messages).expect("every proof should verify");
+            assert_eq!(y, expected_y, "Aggregate key should match sum of shares");
+        }
+    }
+
+    /// Test case where a tampered round-1 message is rejected and the offending pid reported.
+    #[test]
+    fn test_dkg_rejects_tampered_peer_proof() {
+        let base_point = ProjectivePoint::GENERATOR;
+        let sid = "dkg_sid";
+
+        let parties: Vec<DkgParty> = (1..=3)
+            .map(|pid| DkgParty::new(sid, pid, base_point))
+            .collect();
+
+        let mut messages: Vec<DkgRound1Message> = parties.iter().map(|party| party.round1()).collect();
+
+        // Corrupt the second party's response scalar.
+        messages[1].proof.s += Scalar::ONE;
+
+        let result = parties[0].round2(&messages);
+        match result {
+            Err(err) => assert_eq!(err.offending_pid, 2, "Should report the tampered party's pid"),
+            Ok(_) => panic!("Expected round2 to reject the tampered proof"),
+        }
+    }
+}